@@ -1,8 +1,12 @@
 use rand::distributions::{Alphanumeric, DistString};
 use rand::prelude::SliceRandom;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum CheatCodeKind {
     // Mandatory
     Jump,
@@ -32,7 +36,7 @@ pub enum CheatCodeKind {
 }
 
 // here the value is the weight for the weighted distribution
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize)]
 pub enum CheatCodeRarity {
     Mandatory = 0, // weight of zero because it is not present in the distribution
     Common = 10,
@@ -69,15 +73,25 @@ pub struct CheatCode {
     pub text: String,
     pub dependencies: Vec<CheatCodeKind>,
     pub image: String,
+    /// Human-readable name used by UI/hints; never reveals the generated `text`.
+    pub display_name: String,
+    /// Rhai source run by `cheat_effects::run_cheat_code_scripts` the moment
+    /// this code is activated. `None` for codes whose effect is handled by a
+    /// dedicated system instead (e.g. `Jump`, gated directly in
+    /// `runner::player::move_character`).
+    pub script: Option<String>,
 }
 
 impl CheatCode {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         kind: CheatCodeKind,
         rarity: CheatCodeRarity,
         text: &str,
         dependencies: Vec<CheatCodeKind>,
         image: String,
+        display_name: String,
+        script: Option<String>,
     ) -> Self {
         Self {
             kind,
@@ -85,17 +99,71 @@ impl CheatCode {
             text: text.to_string(),
             dependencies,
             image,
+            display_name,
+            script,
         }
     }
 }
 
+/// One row of the `cheat_codes.toml` content file.
+#[derive(Debug, Deserialize)]
+struct CheatCodeEntry {
+    kind: CheatCodeKind,
+    rarity: CheatCodeRarity,
+    #[serde(default)]
+    dependencies: Vec<CheatCodeKind>,
+    image: String,
+    display_name: String,
+    #[serde(default)]
+    script: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheatCodeCatalog {
+    codes: Vec<CheatCodeEntry>,
+}
+
+#[derive(Debug)]
+pub enum CheatCodeLoadError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    UnknownDependency {
+        kind: CheatCodeKind,
+        dependency: CheatCodeKind,
+    },
+    DependencyCycle(Vec<CheatCodeKind>),
+}
+
+impl fmt::Display for CheatCodeLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheatCodeLoadError::Io(err) => write!(f, "failed to read cheat code catalog: {}", err),
+            CheatCodeLoadError::Parse(err) => {
+                write!(f, "failed to parse cheat code catalog: {}", err)
+            }
+            CheatCodeLoadError::UnknownDependency { kind, dependency } => write!(
+                f,
+                "{:?} depends on {:?}, which is not declared in the catalog",
+                kind, dependency
+            ),
+            CheatCodeLoadError::DependencyCycle(cycle) => {
+                write!(f, "dependency cycle detected: {:?}", cycle)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheatCodeLoadError {}
+
 pub struct CheatCodeResource {
     pub codes: HashMap<CheatCodeKind, CheatCode>,
     activated: Vec<CheatCodeKind>,
 }
 
 impl CheatCodeResource {
-    pub fn get_next_code(&self) -> CheatCodeKind {
+    /// The next code the player could unlock, or `None` if every remaining
+    /// code either is already activated or still has an unmet dependency.
+    pub fn get_next_code(&self) -> Option<CheatCodeKind> {
         // first get a list of mandatory cheat codes (JUMP)
         let mandatories = self
             .codes
@@ -108,7 +176,7 @@ impl CheatCodeResource {
             .collect::<Vec<CheatCodeKind>>();
         // if there is a mandatory code to be chosen, then return it
         if !mandatories.is_empty() {
-            return *mandatories.choose(&mut rand::thread_rng()).unwrap();
+            return Some(*mandatories.choose(&mut rand::thread_rng()).unwrap());
         }
 
         // then we grab all the codes that haven't been activated yet
@@ -131,11 +199,11 @@ impl CheatCodeResource {
             .collect::<Vec<&CheatCode>>();
 
         // then return a random code based on their rarity (rarity is the weight)
-
+        // `None` here means every remaining code is blocked on a dependency.
         available_codes
             .choose_weighted(&mut rand::thread_rng(), |code| code.rarity as u8)
-            .unwrap()
-            .kind
+            .ok()
+            .map(|code| code.kind)
     }
 
     pub fn activate_code(&mut self, text: &str) -> CheatCodeActivationResult {
@@ -159,178 +227,121 @@ impl CheatCodeResource {
         self.activated.contains(kind)
     }
 
-    pub fn new() -> Self {
+    /// Loads the full cheat code catalog from a `cheat_codes.toml` content file.
+    ///
+    /// Every dependency listed in the file must reference a kind that is also
+    /// declared in the catalog, and the dependency graph must be acyclic, since
+    /// `get_next_code` walks it assuming both hold.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CheatCodeLoadError> {
+        let raw = fs::read_to_string(path).map_err(CheatCodeLoadError::Io)?;
+        let catalog: CheatCodeCatalog = toml::from_str(&raw).map_err(CheatCodeLoadError::Parse)?;
+
         let mut codes: HashMap<CheatCodeKind, CheatCode> = HashMap::new();
+        for entry in catalog.codes {
+            codes.insert(
+                entry.kind,
+                CheatCode::new(
+                    entry.kind,
+                    entry.rarity,
+                    &generate_random_code(entry.rarity),
+                    entry.dependencies,
+                    entry.image,
+                    entry.display_name,
+                    entry.script,
+                ),
+            );
+        }
 
-        // Mandatory
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::Jump,
-            CheatCodeRarity::Mandatory,
-            vec![],
-            "jump.png",
-        );
+        for code in codes.values() {
+            for dependency in &code.dependencies {
+                if !codes.contains_key(dependency) {
+                    return Err(CheatCodeLoadError::UnknownDependency {
+                        kind: code.kind,
+                        dependency: *dependency,
+                    });
+                }
+            }
+        }
+        detect_dependency_cycle(&codes)?;
 
-        // Common
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::Crouch,
-            CheatCodeRarity::Common,
-            vec![],
-            "crouch.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::Attack,
-            CheatCodeRarity::Common,
-            vec![],
-            "attack.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::AttackDmgBoost,
-            CheatCodeRarity::Common,
-            vec![CheatCodeKind::Attack],
-            "attack_dmg_boost.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::AttackFireRateBoost,
-            CheatCodeRarity::Common,
-            vec![CheatCodeKind::Attack],
-            "attack_fr_boost.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::MoveLeft,
-            CheatCodeRarity::Common,
-            vec![],
-            "move_left.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::SpeedBoost1,
-            CheatCodeRarity::Common,
-            vec![],
-            "speed.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::SpeedBoost2,
-            CheatCodeRarity::Common,
-            vec![CheatCodeKind::SpeedBoost1],
-            "speed.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::SpeedBoost3,
-            CheatCodeRarity::Common,
-            vec![CheatCodeKind::SpeedBoost1, CheatCodeKind::SpeedBoost2],
-            "speed.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::Armor,
-            CheatCodeRarity::Common,
-            vec![],
-            "armor.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::Dash,
-            CheatCodeRarity::Common,
-            vec![],
-            "dash.png",
-        );
+        Ok(Self {
+            codes,
+            activated: Vec::new(),
+        })
+    }
 
-        // Rare
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::DoubleJump,
-            CheatCodeRarity::Rare,
-            vec![CheatCodeKind::Jump],
-            "double_jump.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::SpeedBoost4,
-            CheatCodeRarity::Rare,
-            vec![
-                CheatCodeKind::SpeedBoost1,
-                CheatCodeKind::SpeedBoost2,
-                CheatCodeKind::SpeedBoost3,
-            ],
-            "speed.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::SpeedBoost5,
-            CheatCodeRarity::Rare,
-            vec![
-                CheatCodeKind::SpeedBoost1,
-                CheatCodeKind::SpeedBoost2,
-                CheatCodeKind::SpeedBoost3,
-                CheatCodeKind::SpeedBoost4,
-            ],
-            "speed.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::Shield,
-            CheatCodeRarity::Rare,
-            vec![CheatCodeKind::Jump],
-            "shield.png",
-        );
+    /// The kinds activated so far, in activation order.
+    pub fn activated_kinds(&self) -> &[CheatCodeKind] {
+        &self.activated
+    }
 
-        // Legendary
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::ExtraLife,
-            CheatCodeRarity::Legendary,
-            vec![],
-            "extra_life.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::TempInvicibility,
-            CheatCodeRarity::Legendary,
-            vec![CheatCodeKind::Armor, CheatCodeKind::Shield],
-            "temp_invincibility.png",
-        );
-        insert_cheat(
-            &mut codes,
-            CheatCodeKind::Fly,
-            CheatCodeRarity::Legendary,
-            vec![CheatCodeKind::Jump, CheatCodeKind::DoubleJump],
-            "fly.png",
-        );
+    /// Overwrites the activation list wholesale; used when restoring a save.
+    pub fn restore_activated(&mut self, activated: Vec<CheatCodeKind>) {
+        self.activated = activated;
+    }
 
-        Self {
-            codes,
-            activated: Vec::new(),
+    /// Overwrites a single code's generated text; used when restoring a save
+    /// so a resumed run keeps the secret strings the player already knows.
+    pub fn set_code_text(&mut self, kind: CheatCodeKind, text: String) {
+        if let Some(code) = self.codes.get_mut(&kind) {
+            code.text = text;
         }
     }
 }
 
-fn insert_cheat(
-    codes: &mut HashMap<CheatCodeKind, CheatCode>,
-    kind: CheatCodeKind,
-    rarity: CheatCodeRarity,
-    dependencies: Vec<CheatCodeKind>,
-    image_path: &str,
-) {
-    codes.insert(
-        kind,
-        CheatCode::new(
-            kind,
-            rarity,
-            &generate_random_code(rarity),
-            dependencies,
-            image_path.to_string(),
-        ),
-    );
+/// Depth-first search over the dependency graph so that a malformed catalog
+/// (e.g. `A` depends on `B` depends on `A`) is rejected at load time instead
+/// of leaving the codes on the cycle permanently locked: `get_next_code`
+/// never offers a code whose dependencies aren't all activated.
+fn detect_dependency_cycle(
+    codes: &HashMap<CheatCodeKind, CheatCode>,
+) -> Result<(), CheatCodeLoadError> {
+    #[derive(PartialEq)]
+    enum Mark {
+        Visiting,
+        Done,
+    }
+
+    let mut marks: HashMap<CheatCodeKind, Mark> = HashMap::new();
+    let mut stack: Vec<CheatCodeKind> = Vec::new();
+
+    fn visit(
+        kind: CheatCodeKind,
+        codes: &HashMap<CheatCodeKind, CheatCode>,
+        marks: &mut HashMap<CheatCodeKind, Mark>,
+        stack: &mut Vec<CheatCodeKind>,
+    ) -> Result<(), CheatCodeLoadError> {
+        match marks.get(&kind) {
+            Some(Mark::Done) => return Ok(()),
+            Some(Mark::Visiting) => {
+                stack.push(kind);
+                let cycle_start = stack.iter().position(|k| *k == kind).unwrap();
+                return Err(CheatCodeLoadError::DependencyCycle(
+                    stack[cycle_start..].to_vec(),
+                ));
+            }
+            None => {}
+        }
+
+        marks.insert(kind, Mark::Visiting);
+        stack.push(kind);
+        for dependency in &codes[&kind].dependencies {
+            visit(*dependency, codes, marks, stack)?;
+        }
+        stack.pop();
+        marks.insert(kind, Mark::Done);
+        Ok(())
+    }
+
+    for kind in codes.keys() {
+        visit(*kind, codes, &mut marks, &mut stack)?;
+    }
+    Ok(())
 }
 
+/// Generates a code's display text. The generated strings aren't meant to be
+/// reproducible across a restart; `save::SaveData` dumps every code's exact
+/// `text` instead of regenerating it.
 pub fn generate_random_code(rarity: CheatCodeRarity) -> String {
     // length is based on the rarity
     let length = match rarity {
@@ -341,4 +352,70 @@ pub fn generate_random_code(rarity: CheatCodeRarity) -> String {
     };
 
     Alphanumeric.sample_string(&mut rand::thread_rng(), length)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_temp_catalog(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let mut file = fs::File::create(&path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn from_file_rejects_unknown_dependency() {
+        let path = write_temp_catalog(
+            "bevy_jam_cheat_codes_unknown_dependency.toml",
+            r#"
+                [[codes]]
+                kind = "Jump"
+                rarity = "Mandatory"
+                dependencies = ["DoubleJump"]
+                image = "jump.png"
+                display_name = "Jump"
+            "#,
+        );
+
+        let result = CheatCodeResource::from_file(&path);
+        fs::remove_file(&path).ok();
+
+        assert!(matches!(
+            result,
+            Err(CheatCodeLoadError::UnknownDependency { .. })
+        ));
+    }
+
+    #[test]
+    fn from_file_rejects_dependency_cycle() {
+        let path = write_temp_catalog(
+            "bevy_jam_cheat_codes_dependency_cycle.toml",
+            r#"
+                [[codes]]
+                kind = "Jump"
+                rarity = "Mandatory"
+                dependencies = ["DoubleJump"]
+                image = "jump.png"
+                display_name = "Jump"
+
+                [[codes]]
+                kind = "DoubleJump"
+                rarity = "Rare"
+                dependencies = ["Jump"]
+                image = "double_jump.png"
+                display_name = "Double Jump"
+            "#,
+        );
+
+        let result = CheatCodeResource::from_file(&path);
+        fs::remove_file(&path).ok();
+
+        assert!(matches!(
+            result,
+            Err(CheatCodeLoadError::DependencyCycle(_))
+        ));
+    }
+}
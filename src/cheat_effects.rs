@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rhai::{Engine, Scope};
+use std::collections::{HashMap, HashSet};
+
+use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
+use crate::runner::player::{DefenseCharges, Player};
+use crate::states::GameStates;
+
+pub const INVINCIBILITY_TIMER: &str = "invincibility";
+
+pub struct CheatEffectsPlugin;
+
+impl Plugin for CheatEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TempEffectTimers::default())
+            .init_resource::<AppliedCheatEffects>()
+            .add_system_set(
+                SystemSet::on_update(GameStates::Main)
+                    .with_system(run_cheat_code_scripts)
+                    .with_system(tick_temp_effect_timers),
+            );
+    }
+}
+
+/// Which codes' scripts have already run this run, so a code only ever grants
+/// its `add_lives`/`add_armor`/etc. effect once. A resource rather than a
+/// `Local` so `save::load_save` can pre-mark codes restored from a save file
+/// as already applied, instead of their one-shot scripts firing again for
+/// effects the save already accounts for.
+#[derive(Default)]
+pub struct AppliedCheatEffects(HashSet<CheatCodeKind>);
+
+impl AppliedCheatEffects {
+    pub fn mark_applied(&mut self, kinds: impl IntoIterator<Item = CheatCodeKind>) {
+        self.0.extend(kinds);
+    }
+}
+
+/// Named countdowns granted by a cheat script's `grant_invincibility(secs)`
+/// call, so temporary effects expire on their own without a bespoke system
+/// per effect.
+#[derive(Default)]
+pub struct TempEffectTimers {
+    timers: HashMap<String, Timer>,
+}
+
+impl TempEffectTimers {
+    pub fn is_active(&self, name: &str) -> bool {
+        self.timers
+            .get(name)
+            .map_or(false, |timer| !timer.finished())
+    }
+
+    fn start(&mut self, name: &str, secs: f32) {
+        self.timers
+            .insert(name.to_string(), Timer::from_seconds(secs, false));
+    }
+}
+
+fn tick_temp_effect_timers(time: Res<Time>, mut timers: ResMut<TempEffectTimers>) {
+    for timer in timers.timers.values_mut() {
+        timer.tick(time.delta());
+    }
+}
+
+/// Scratchpad a cheat script writes into through the registered engine
+/// functions. Applying it to the player happens after the script finishes,
+/// so gameplay state never needs to be borrowed from inside a Rhai callback.
+#[derive(Clone, Default)]
+struct CheatScriptEffects {
+    speed_multiplier: f32,
+    lives_delta: i32,
+    impulse: (f32, f32),
+    invincibility_secs: f32,
+    armor_charges: u8,
+    shield_charges: u8,
+}
+
+fn build_script_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.register_type::<CheatScriptEffects>();
+    engine.register_fn(
+        "set_speed",
+        |fx: &mut CheatScriptEffects, multiplier: f64| {
+            fx.speed_multiplier = multiplier as f32;
+        },
+    );
+    engine.register_fn("add_lives", |fx: &mut CheatScriptEffects, amount: i64| {
+        fx.lives_delta += amount as i32;
+    });
+    engine.register_fn(
+        "apply_impulse",
+        |fx: &mut CheatScriptEffects, x: f64, y: f64| {
+            fx.impulse = (x as f32, y as f32);
+        },
+    );
+    engine.register_fn(
+        "grant_invincibility",
+        |fx: &mut CheatScriptEffects, secs: f64| {
+            fx.invincibility_secs = secs as f32;
+        },
+    );
+    engine.register_fn("add_armor", |fx: &mut CheatScriptEffects, amount: i64| {
+        fx.armor_charges += amount as u8;
+    });
+    engine.register_fn("add_shield", |fx: &mut CheatScriptEffects, amount: i64| {
+        fx.shield_charges += amount as u8;
+    });
+    engine
+}
+
+/// Runs the Rhai script attached to each newly-activated cheat code against
+/// the exposed effect API, then applies the result to the player. This keeps
+/// effect behavior in `cheat_codes.toml` scripts instead of branching in
+/// `move_character`/`player_collide_enemy`.
+fn run_cheat_code_scripts(
+    cheat_codes: Res<CheatCodeResource>,
+    mut already_applied: ResMut<AppliedCheatEffects>,
+    mut player_query: Query<(
+        &mut Player,
+        &mut RigidBodyVelocityComponent,
+        &mut DefenseCharges,
+    )>,
+    mut timers: ResMut<TempEffectTimers>,
+) {
+    let newly_activated: Vec<&CheatCodeKind> = cheat_codes
+        .codes
+        .keys()
+        .filter(|kind| cheat_codes.is_code_activated(kind) && !already_applied.0.contains(kind))
+        .collect();
+
+    if newly_activated.is_empty() {
+        return;
+    }
+
+    let engine = build_script_engine();
+
+    for kind in newly_activated {
+        already_applied.0.insert(*kind);
+
+        let script = match cheat_codes.codes[kind].script.as_ref() {
+            Some(script) => script,
+            None => continue,
+        };
+
+        let mut scope = Scope::new();
+        scope.push("fx", CheatScriptEffects::default());
+        if let Err(err) = engine.run_with_scope(&mut scope, script) {
+            error!("cheat script for {:?} failed: {}", kind, err);
+            continue;
+        }
+        let effects = scope
+            .get_value::<CheatScriptEffects>("fx")
+            .unwrap_or_default();
+
+        for (mut player, mut rb_vel, mut defense) in player_query.iter_mut() {
+            if effects.speed_multiplier != 0.0 {
+                player.speed *= effects.speed_multiplier;
+            }
+            if effects.lives_delta != 0 {
+                player.lives += effects.lives_delta;
+            }
+            if effects.impulse != (0.0, 0.0) {
+                rb_vel.linvel.x += effects.impulse.0;
+                rb_vel.linvel.y += effects.impulse.1;
+            }
+            if effects.invincibility_secs > 0.0 {
+                timers.start(INVINCIBILITY_TIMER, effects.invincibility_secs);
+            }
+            defense.armor += effects.armor_charges;
+            defense.shield += effects.shield_charges;
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+
+use crate::cheat_codes::{CheatCodeRarity, CheatCodeResource};
+use crate::states::GameStates;
+
+const SCROLLBACK_LEN: usize = 8;
+
+/// In-game overlay for typing and submitting cheat codes by hand.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ConsoleState::default())
+            .add_system_set(SystemSet::on_enter(GameStates::Main).with_system(spawn_console_ui))
+            .add_system_set(
+                SystemSet::on_update(GameStates::Main)
+                    .with_system(toggle_console)
+                    .with_system(buffer_console_input.after(toggle_console))
+                    .with_system(render_console_ui.after(buffer_console_input)),
+            );
+    }
+}
+
+/// Buffered keystrokes, submission scrollback, and up/down history for the
+/// cheat console. `hinted_kind` is refreshed from `get_next_code` each time
+/// the console is opened, standing in for "the player reached a pickup"
+/// until a dedicated pickup subsystem exists.
+#[derive(Default)]
+pub struct ConsoleState {
+    pub open: bool,
+    pub buffer: String,
+    history: Vec<String>,
+    history_cursor: Option<usize>,
+    scrollback: Vec<String>,
+    hinted_kind: Option<crate::cheat_codes::CheatCodeKind>,
+}
+
+impl ConsoleState {
+    /// Whether the console is currently open; gameplay input systems check
+    /// this so typing a code doesn't also drive the player.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn push_scrollback(&mut self, line: String) {
+        self.scrollback.push(line);
+        if self.scrollback.len() > SCROLLBACK_LEN {
+            self.scrollback.remove(0);
+        }
+    }
+
+    fn submit(&mut self, cheat_codes: &mut CheatCodeResource) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let result = cheat_codes.activate_code(&self.buffer);
+        self.push_scrollback(format!("> {}", self.buffer));
+        self.push_scrollback(result.repr());
+        self.history.push(std::mem::take(&mut self.buffer));
+        self.history_cursor = None;
+    }
+
+    fn recall_older(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+        let index = match self.history_cursor {
+            Some(i) if i > 0 => i - 1,
+            Some(i) => i,
+            None => self.history.len() - 1,
+        };
+        self.history_cursor = Some(index);
+        self.buffer = self.history[index].clone();
+    }
+
+    fn recall_newer(&mut self) {
+        match self.history_cursor {
+            Some(i) if i + 1 < self.history.len() => {
+                self.history_cursor = Some(i + 1);
+                self.buffer = self.history[i + 1].clone();
+            }
+            Some(_) => {
+                self.history_cursor = None;
+                self.buffer.clear();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Length shown in the hint for a code of the given rarity, without
+/// revealing the generated text itself.
+fn hint_length(rarity: CheatCodeRarity) -> usize {
+    match rarity {
+        CheatCodeRarity::Mandatory | CheatCodeRarity::Common => 4,
+        CheatCodeRarity::Rare => 6,
+        CheatCodeRarity::Legendary => 8,
+    }
+}
+
+#[derive(Component)]
+struct ConsoleRootNode;
+
+#[derive(Component)]
+struct ConsoleHintText;
+
+#[derive(Component)]
+struct ConsoleScrollbackText;
+
+#[derive(Component)]
+struct ConsoleBufferText;
+
+fn spawn_console_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let text_style = |color: Color, font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: Rect::all(Val::Px(8.0)),
+                display: Display::None,
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+            ..Default::default()
+        })
+        .insert(ConsoleRootNode)
+        .insert(Name::new("CheatConsole"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        text_style(Color::YELLOW, 16.0),
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(ConsoleHintText);
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section("", text_style(Color::GRAY, 14.0), Default::default()),
+                    ..Default::default()
+                })
+                .insert(ConsoleScrollbackText);
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "> ",
+                        text_style(Color::WHITE, 18.0),
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(ConsoleBufferText);
+        });
+}
+
+fn toggle_console(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut console: ResMut<ConsoleState>,
+    cheat_codes: Res<CheatCodeResource>,
+    mut root_query: Query<&mut Style, With<ConsoleRootNode>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Grave) {
+        return;
+    }
+
+    console.open = !console.open;
+    if console.open {
+        console.hinted_kind = cheat_codes.get_next_code();
+    }
+    for mut style in root_query.iter_mut() {
+        style.display = if console.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+fn buffer_console_input(
+    mut char_input_events: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut console: ResMut<ConsoleState>,
+    mut cheat_codes: ResMut<CheatCodeResource>,
+) {
+    if !console.open {
+        char_input_events.iter().for_each(drop);
+        return;
+    }
+
+    for event in char_input_events.iter() {
+        // The backtick that just opened (or closed) the console also arrives
+        // here as a character event; drop it instead of typing it in.
+        if event.char.is_ascii_graphic() && event.char != '`' {
+            console.buffer.push(event.char);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        console.buffer.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        console.submit(&mut cheat_codes);
+    }
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        console.recall_older();
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        console.recall_newer();
+    }
+}
+
+fn render_console_ui(
+    console: Res<ConsoleState>,
+    cheat_codes: Res<CheatCodeResource>,
+    mut hint_query: Query<
+        &mut Text,
+        (
+            With<ConsoleHintText>,
+            Without<ConsoleScrollbackText>,
+            Without<ConsoleBufferText>,
+        ),
+    >,
+    mut scrollback_query: Query<
+        &mut Text,
+        (
+            With<ConsoleScrollbackText>,
+            Without<ConsoleHintText>,
+            Without<ConsoleBufferText>,
+        ),
+    >,
+    mut buffer_query: Query<
+        &mut Text,
+        (
+            With<ConsoleBufferText>,
+            Without<ConsoleHintText>,
+            Without<ConsoleScrollbackText>,
+        ),
+    >,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for mut text in hint_query.iter_mut() {
+        text.sections[0].value = match console.hinted_kind {
+            Some(kind) => {
+                let rarity = cheat_codes.codes[&kind].rarity;
+                format!("Next unlock: {:?}, {} chars", rarity, hint_length(rarity))
+            }
+            None => String::new(),
+        };
+    }
+
+    for mut text in scrollback_query.iter_mut() {
+        text.sections[0].value = console.scrollback.join("\n");
+    }
+
+    for mut text in buffer_query.iter_mut() {
+        text.sections[0].value = format!("> {}", console.buffer);
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::runner::player::{GameOverEvent, Ground, Player};
+use crate::states::GameStates;
+
+/// Arena dimensions and wall thickness for the current level; swap this
+/// resource's values (or replace it per-level) instead of hardcoding bounds.
+pub struct LevelBoundsConfig {
+    pub width: f32,
+    pub height: f32,
+    pub wall_thickness: f32,
+    /// Distance below the floor (itself at `-height / 2`) that the kill zone
+    /// sits at, replacing the old hardcoded fall-death check. Must stay
+    /// positive so the kill zone can't end up above the `Ground`-tagged
+    /// floor and intercept the player before they land on it.
+    pub kill_zone_margin: f32,
+}
+
+impl Default for LevelBoundsConfig {
+    fn default() -> Self {
+        Self {
+            width: 2000.0,
+            height: 1000.0,
+            wall_thickness: 20.0,
+            kill_zone_margin: 400.0,
+        }
+    }
+}
+
+/// Sensor region that ends the run via `GameOverEvent` as soon as the player
+/// enters it.
+#[derive(Component)]
+pub struct KillZone;
+
+pub struct LevelBoundsPlugin;
+
+impl Plugin for LevelBoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelBoundsConfig>()
+            .add_system_set(
+                SystemSet::on_enter(GameStates::Main)
+                    .with_system(spawn_level_bounds.after("setup_physics")),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameStates::Main).with_system(detect_kill_zone_entry),
+            );
+    }
+}
+
+fn spawn_wall(commands: &mut Commands, position: Vec2, half_extents: Vec2, name: &str) -> Entity {
+    commands
+        .spawn_bundle(ColliderBundle {
+            shape: ColliderShape::cuboid(half_extents.x, half_extents.y).into(),
+            position: position.into(),
+            ..Default::default()
+        })
+        .insert(ColliderPositionSync::Discrete)
+        .insert(Name::new(name.to_string()))
+        .id()
+}
+
+fn spawn_level_bounds(
+    mut commands: Commands,
+    config: Res<LevelBoundsConfig>,
+    rapier_config: Res<RapierConfiguration>,
+) {
+    let half_width = config.width / 2.0 / rapier_config.scale;
+    let half_height = config.height / 2.0 / rapier_config.scale;
+    let half_thickness = config.wall_thickness / 2.0 / rapier_config.scale;
+
+    spawn_wall(
+        &mut commands,
+        Vec2::new(-half_width, 0.0),
+        Vec2::new(half_thickness, half_height),
+        "LeftWall",
+    );
+    spawn_wall(
+        &mut commands,
+        Vec2::new(half_width, 0.0),
+        Vec2::new(half_thickness, half_height),
+        "RightWall",
+    );
+    spawn_wall(
+        &mut commands,
+        Vec2::new(0.0, half_height),
+        Vec2::new(half_width, half_thickness),
+        "TopWall",
+    );
+
+    // Tagged `Ground` so the player's ground sensor (see `runner::player`)
+    // registers a landing here; without this the jump/double-jump gating
+    // never sees `on_ground` go true.
+    let floor = spawn_wall(
+        &mut commands,
+        Vec2::new(0.0, -half_height),
+        Vec2::new(half_width, half_thickness),
+        "Floor",
+    );
+    commands.entity(floor).insert(Ground);
+
+    commands
+        .spawn_bundle(ColliderBundle {
+            shape: ColliderShape::cuboid(half_width, half_thickness).into(),
+            collider_type: ColliderType::Sensor.into(),
+            position: Vec2::new(
+                0.0,
+                -half_height - config.kill_zone_margin / rapier_config.scale,
+            )
+            .into(),
+            flags: ColliderFlags {
+                active_events: ActiveEvents::INTERSECTION_EVENTS,
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        })
+        .insert(ColliderPositionSync::Discrete)
+        .insert(KillZone)
+        .insert(Name::new("KillZone"));
+}
+
+fn detect_kill_zone_entry(
+    mut intersection_events: EventReader<IntersectionEvent>,
+    player_query: Query<Entity, With<Player>>,
+    kill_zone_query: Query<Entity, With<KillZone>>,
+    mut game_over_event: EventWriter<GameOverEvent>,
+) {
+    for event in intersection_events.iter() {
+        if !event.intersecting {
+            continue;
+        }
+        let pairs = [
+            (event.collider1.entity(), event.collider2.entity()),
+            (event.collider2.entity(), event.collider1.entity()),
+        ];
+        for (player_entity, kill_zone_entity) in pairs {
+            if player_query.get(player_entity).is_ok()
+                && kill_zone_query.get(kill_zone_entity).is_ok()
+            {
+                game_over_event.send(GameOverEvent);
+                info!("Fell into a kill zone");
+            }
+        }
+    }
+}
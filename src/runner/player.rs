@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
 use crate::enemies::Enemy;
 use crate::{camera::TwoDCameraComponent, physics, states::GameStates};
 use bevy::{prelude::*, render::camera::Camera};
@@ -5,6 +9,8 @@ use bevy_rapier2d::prelude::*;
 
 use super::CollectedChars;
 use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
+use crate::cheat_effects::{TempEffectTimers, INVINCIBILITY_TIMER};
+use crate::console::ConsoleState;
 use crate::interactables::{CharTextComponent, InteractableComponent, InteractableType};
 
 #[derive(Debug, Component)]
@@ -18,28 +24,165 @@ pub struct Player {
 #[derive(Component)]
 pub struct PlayerAnimationTimer(Timer);
 
+/// What the player is currently doing, used to pick which sprite sheet and
+/// frame range `animate_sprite` plays.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayerActivity {
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Crouch,
+    Attack,
+    Dash,
+}
+
+impl PlayerActivity {
+    /// Whether this activity should keep cycling its frame range, as opposed
+    /// to playing once and falling back to the activity that preceded it.
+    fn loops(self) -> bool {
+        !matches!(self, PlayerActivity::Attack)
+    }
+}
+
+/// Per-entity table mapping each `PlayerActivity` to the atlas and frame
+/// range that represents it, built once in `spawn_character`.
+#[derive(Component)]
+pub struct PlayerAnimationSet {
+    frames: HashMap<PlayerActivity, (Handle<TextureAtlas>, Range<usize>)>,
+}
+
+impl PlayerAnimationSet {
+    pub fn new(frames: HashMap<PlayerActivity, (Handle<TextureAtlas>, Range<usize>)>) -> Self {
+        Self { frames }
+    }
+
+    fn frame_range(&self, activity: PlayerActivity) -> Range<usize> {
+        self.frames
+            .get(&activity)
+            .map(|(_, range)| range.clone())
+            .unwrap_or(0..1)
+    }
+
+    fn atlas(&self, activity: PlayerActivity) -> Option<&Handle<TextureAtlas>> {
+        self.frames.get(&activity).map(|(atlas, _)| atlas)
+    }
+}
+
+/// Tracks which activity is playing and how far into its frame range we are,
+/// plus what to fall back to once a non-looping activity finishes.
+#[derive(Component)]
+pub struct PlayerAnimationState {
+    pub activity: PlayerActivity,
+    previous_activity: PlayerActivity,
+    frame_in_activity: usize,
+}
+
+impl Default for PlayerAnimationState {
+    fn default() -> Self {
+        Self {
+            activity: PlayerActivity::Idle,
+            previous_activity: PlayerActivity::Idle,
+            frame_in_activity: 0,
+        }
+    }
+}
+
+/// Whether the player is currently touching the ground, maintained by
+/// `update_grounded` from the ground sensor's intersection events.
+#[derive(Component, Default)]
+pub struct Grounded {
+    pub on_ground: bool,
+    pub jumps_used: u8,
+}
+
+/// Tag applied to the small sensor collider spawned beneath the player;
+/// `update_grounded` uses it to tell the ground sensor apart from the
+/// player's own body collider.
+#[derive(Component)]
+struct GroundSensor(Entity);
+
+/// Tag applied to floor/platform colliders so `update_grounded` can
+/// recognize what the sensor is actually resting on.
+#[derive(Component)]
+pub struct Ground;
+
+/// Armor/Shield charges granted by their cheat scripts (see `cheat_effects`);
+/// `player_collide_enemy` consumes these before a hit costs a life.
+#[derive(Component, Default)]
+pub struct DefenseCharges {
+    pub armor: u8,
+    pub shield: u8,
+}
+
+/// Tracks double-tap and cooldown state for the `Dash` cheat code.
+#[derive(Component)]
+pub struct DashState {
+    last_left_tap: f64,
+    last_right_tap: f64,
+    last_dash: f64,
+}
+
+impl Default for DashState {
+    fn default() -> Self {
+        Self {
+            last_left_tap: f64::NEG_INFINITY,
+            last_right_tap: f64::NEG_INFINITY,
+            last_dash: f64::NEG_INFINITY,
+        }
+    }
+}
+
+const DASH_DOUBLE_TAP_WINDOW: f64 = 0.3;
+const DASH_COOLDOWN: f64 = 1.0;
+const DASH_SPEED: f32 = 20.0;
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(CollectedChars { values: Vec::new() })
             .add_system_set(
-                SystemSet::on_enter(GameStates::Main)
-                    .with_system(spawn_character.after("setup_physics")),
+                SystemSet::on_enter(GameStates::Main).with_system(
+                    spawn_character
+                        .label("spawn_character")
+                        .after("setup_physics"),
+                ),
             )
             .add_event::<GameOverEvent>()
             .add_system_set(
                 SystemSet::on_update(GameStates::Main)
                     .with_system(follow_player_camera)
-                    .with_system(animate_sprite)
+                    .with_system(update_grounded)
+                    .with_system(update_player_activity.before("animate_sprite"))
+                    .with_system(animate_sprite.label("animate_sprite"))
                     .with_system(move_character)
                     .with_system(detect_char_interactable)
-                    .with_system(player_collide_enemy)
-                    .with_system(player_fall_damage),
+                    .with_system(player_collide_enemy),
             );
     }
 }
 
+/// Loads a `columns`-wide, 24x24 sprite sheet for a single activity. If the
+/// sheet isn't in `assets/` (the extra per-activity sheets aren't part of
+/// every asset pack this game has shipped with), falls back to `fallback`
+/// instead of leaving the activity rendering a blank/garbage frame.
+fn load_activity_atlas(
+    asset_server: &AssetServer,
+    texture_atlases: &mut Assets<TextureAtlas>,
+    path: &str,
+    columns: usize,
+    fallback: &Handle<TextureAtlas>,
+) -> Handle<TextureAtlas> {
+    if !Path::new("assets").join(path).exists() {
+        warn!("missing sprite sheet {}, reusing the idle/run sheet", path);
+        return fallback.clone();
+    }
+    let texture_handle = asset_server.load(path);
+    let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(24.0, 24.0), columns, 1);
+    texture_atlases.add(texture_atlas)
+}
+
 /// Spawns our character and loads it's resources
 fn spawn_character(
     mut commands: Commands,
@@ -47,9 +190,59 @@ fn spawn_character(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     rapier_config: Res<RapierConfiguration>,
 ) {
-    let texture_handle = asset_server.load("gabe-idle-run.png");
-    let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(24.0, 24.0), 7, 1);
-    let texture_atlas_handle = texture_atlases.add(texture_atlas);
+    let idle_run_texture = asset_server.load("gabe-idle-run.png");
+    let idle_run_atlas = texture_atlases.add(TextureAtlas::from_grid(
+        idle_run_texture,
+        Vec2::new(24.0, 24.0),
+        7,
+        1,
+    ));
+    let jump_atlas = load_activity_atlas(
+        &asset_server,
+        &mut texture_atlases,
+        "gabe-jump.png",
+        1,
+        &idle_run_atlas,
+    );
+    let fall_atlas = load_activity_atlas(
+        &asset_server,
+        &mut texture_atlases,
+        "gabe-fall.png",
+        1,
+        &idle_run_atlas,
+    );
+    let crouch_atlas = load_activity_atlas(
+        &asset_server,
+        &mut texture_atlases,
+        "gabe-crouch.png",
+        1,
+        &idle_run_atlas,
+    );
+    let attack_atlas = load_activity_atlas(
+        &asset_server,
+        &mut texture_atlases,
+        "gabe-attack.png",
+        4,
+        &idle_run_atlas,
+    );
+    let dash_atlas = load_activity_atlas(
+        &asset_server,
+        &mut texture_atlases,
+        "gabe-dash.png",
+        1,
+        &idle_run_atlas,
+    );
+
+    let mut frames = HashMap::new();
+    frames.insert(PlayerActivity::Idle, (idle_run_atlas.clone(), 0..4));
+    frames.insert(PlayerActivity::Run, (idle_run_atlas.clone(), 4..7));
+    frames.insert(PlayerActivity::Jump, (jump_atlas, 0..1));
+    frames.insert(PlayerActivity::Fall, (fall_atlas, 0..1));
+    frames.insert(PlayerActivity::Crouch, (crouch_atlas, 0..1));
+    frames.insert(PlayerActivity::Attack, (attack_atlas, 0..4));
+    frames.insert(PlayerActivity::Dash, (dash_atlas, 0..1));
+    let animations = PlayerAnimationSet::new(frames);
+
     let player = Player {
         speed: 8.0,
         acceleration: 0.09,
@@ -60,9 +253,9 @@ fn spawn_character(
     let collider_size_hx = 24.0 * 2.0 / rapier_config.scale / 2.0;
     let collider_size_hy = 24.0 * 2.0 / rapier_config.scale / 2.0;
 
-    commands
+    let player_entity = commands
         .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle,
+            texture_atlas: idle_run_atlas,
             transform: Transform {
                 scale: Vec3::new(2.0, 2.0, 1.0),
                 translation: Vec3::new(0.0, 0.0, 100.0),
@@ -93,46 +286,190 @@ fn spawn_character(
         })
         .insert(ColliderPositionSync::Discrete)
         .insert(PlayerAnimationTimer(Timer::from_seconds(0.1, true)))
+        .insert(animations)
+        .insert(PlayerAnimationState::default())
+        .insert(Grounded::default())
+        .insert(DefenseCharges::default())
+        .insert(DashState::default())
         .insert(Name::new("Player"))
-        .insert(player);
+        .insert(player)
+        .id();
+
+    // A thin sensor strip beneath the player's feet; `update_grounded` reads
+    // its intersection events instead of inferring "on the ground" from
+    // velocity, so standing still on a platform still counts as grounded.
+    let sensor_size_hx = collider_size_hx * 0.9;
+    let sensor_size_hy = 2.0 / rapier_config.scale;
+    commands.entity(player_entity).with_children(|parent| {
+        parent
+            .spawn_bundle(ColliderBundle {
+                shape: ColliderShape::cuboid(sensor_size_hx, sensor_size_hy).into(),
+                collider_type: ColliderType::Sensor.into(),
+                position: Vec2::new(0.0, -collider_size_hy - sensor_size_hy).into(),
+                flags: ColliderFlags {
+                    active_events: ActiveEvents::INTERSECTION_EVENTS,
+                    ..Default::default()
+                }
+                .into(),
+                ..Default::default()
+            })
+            .insert(ColliderPositionSync::Discrete)
+            .insert(GroundSensor(player_entity));
+    });
+}
+
+/// Updates `Grounded` from the ground sensor's intersection events: entering
+/// a `Ground`-tagged collider means the player has landed, leaving one means
+/// they're airborne again.
+fn update_grounded(
+    mut intersection_events: EventReader<IntersectionEvent>,
+    sensor_query: Query<&GroundSensor>,
+    ground_query: Query<Entity, With<Ground>>,
+    mut grounded_query: Query<&mut Grounded>,
+) {
+    for event in intersection_events.iter() {
+        let pairs = [
+            (event.collider1.entity(), event.collider2.entity()),
+            (event.collider2.entity(), event.collider1.entity()),
+        ];
+        for (sensor_entity, other_entity) in pairs {
+            if let Ok(GroundSensor(player_entity)) = sensor_query.get(sensor_entity) {
+                if ground_query.get(other_entity).is_ok() {
+                    if let Ok(mut grounded) = grounded_query.get_mut(*player_entity) {
+                        grounded.on_ground = event.intersecting;
+                        if grounded.on_ground {
+                            grounded.jumps_used = 0;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Picks the player's current activity from input and vertical/horizontal
+/// velocity. `animate_sprite` reacts to the change by resetting its frame
+/// index and swapping to the matching atlas.
+fn update_player_activity(
+    keyboard_input: Res<Input<KeyCode>>,
+    cheat_codes: Res<CheatCodeResource>,
+    console: Res<ConsoleState>,
+    mut query: Query<(&mut PlayerAnimationState, &RigidBodyVelocityComponent)>,
+) {
+    if console.is_open() {
+        return;
+    }
+
+    const MOVING_THRESHOLD: f32 = 1.0;
+    const AIRBORNE_THRESHOLD: f32 = 10.0;
+
+    for (mut state, rb_vel) in query.iter_mut() {
+        // Attack is a one-shot activity: once triggered, let `animate_sprite`
+        // play it to completion and fall back on its own.
+        if state.activity == PlayerActivity::Attack {
+            continue;
+        }
+
+        let attacking = cheat_codes.is_code_activated(&CheatCodeKind::Attack)
+            && keyboard_input.just_pressed(KeyCode::F);
+        let crouching = cheat_codes.is_code_activated(&CheatCodeKind::Crouch)
+            && keyboard_input.pressed(KeyCode::S);
+
+        let next_activity = if attacking {
+            PlayerActivity::Attack
+        } else if rb_vel.linvel.y > AIRBORNE_THRESHOLD {
+            PlayerActivity::Jump
+        } else if rb_vel.linvel.y < -AIRBORNE_THRESHOLD {
+            PlayerActivity::Fall
+        } else if crouching {
+            PlayerActivity::Crouch
+        } else if rb_vel.linvel.x.abs() > MOVING_THRESHOLD {
+            PlayerActivity::Run
+        } else {
+            PlayerActivity::Idle
+        };
+
+        if next_activity != state.activity {
+            state.previous_activity = state.activity;
+            state.activity = next_activity;
+            state.frame_in_activity = 0;
+        }
+    }
 }
 
 pub fn animate_sprite(
     time: Res<Time>,
-    texture_atlases: Res<Assets<TextureAtlas>>,
     mut query: Query<(
         &mut PlayerAnimationTimer,
+        &mut PlayerAnimationState,
+        &PlayerAnimationSet,
         &mut TextureAtlasSprite,
-        &Handle<TextureAtlas>,
+        &mut Handle<TextureAtlas>,
     )>,
 ) {
-    for (mut timer, mut sprite, texture_atlas_handle) in query.iter_mut() {
+    for (mut timer, mut state, animations, mut sprite, mut atlas_handle) in query.iter_mut() {
         timer.0.tick(time.delta());
-        if timer.0.just_finished() {
-            let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
-            sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
+        if !timer.0.just_finished() {
+            continue;
+        }
+
+        if let Some(atlas) = animations.atlas(state.activity) {
+            if *atlas_handle != *atlas {
+                *atlas_handle = atlas.clone();
+            }
+        }
+
+        // Render the frame we're currently on before advancing, so a
+        // just-reset `frame_in_activity` of 0 actually shows `range.start`
+        // instead of skipping straight to the second frame.
+        let range = animations.frame_range(state.activity);
+        sprite.index = range.start + state.frame_in_activity;
+
+        state.frame_in_activity += 1;
+        if state.frame_in_activity >= range.len() {
+            if state.activity.loops() {
+                state.frame_in_activity = 0;
+            } else {
+                state.activity = state.previous_activity;
+                state.frame_in_activity = 0;
+            }
         }
     }
 }
 
 fn move_character(
+    time: Res<Time>,
     keyboard_input: Res<Input<KeyCode>>,
     rapier_config: Res<RapierConfiguration>,
     mut query: Query<(
         &Player,
+        &mut Grounded,
+        &mut DashState,
         &mut RigidBodyVelocityComponent,
         &RigidBodyMassPropsComponent,
     )>,
-    cheat_codes: ResMut<CheatCodeResource>,
+    cheat_codes: Res<CheatCodeResource>,
+    console: Res<ConsoleState>,
 ) {
-    for (player, mut rb_vel, rb_mprops) in query.iter_mut() {
+    if console.is_open() {
+        return;
+    }
+
+    for (player, mut grounded, mut dash_state, mut rb_vel, rb_mprops) in query.iter_mut() {
         let _up = keyboard_input.pressed(KeyCode::W);
         let _down = keyboard_input.pressed(KeyCode::S);
-        let left = keyboard_input.pressed(KeyCode::A);
+        let left = keyboard_input.pressed(KeyCode::A)
+            && cheat_codes.is_code_activated(&CheatCodeKind::MoveLeft);
         let right = keyboard_input.pressed(KeyCode::D);
 
-        let jump = cheat_codes.is_code_activated(&CheatCodeKind::Jump)
+        let flying = cheat_codes.is_code_activated(&CheatCodeKind::Fly)
+            && keyboard_input.pressed(KeyCode::Space);
+
+        let jump_requested = cheat_codes.is_code_activated(&CheatCodeKind::Jump)
             && keyboard_input.just_released(KeyCode::Space);
+        let double_jump_available =
+            cheat_codes.is_code_activated(&CheatCodeKind::DoubleJump) && grounded.jumps_used < 2;
+        let jump = jump_requested && !flying && (grounded.on_ground || double_jump_available);
 
         let x_axis = -(left as i8) + right as i8;
 
@@ -151,8 +488,35 @@ fn move_character(
             rb_vel.linvel.x = 0.0;
         }
 
-        if jump {
-            physics::jump(700.0, &mut rb_vel, rb_mprops)
+        if cheat_codes.is_code_activated(&CheatCodeKind::Dash) {
+            let now = time.seconds_since_startup();
+            let off_cooldown = now - dash_state.last_dash > DASH_COOLDOWN;
+
+            if keyboard_input.just_pressed(KeyCode::A)
+                && cheat_codes.is_code_activated(&CheatCodeKind::MoveLeft)
+            {
+                if off_cooldown && now - dash_state.last_left_tap < DASH_DOUBLE_TAP_WINDOW {
+                    rb_vel.linvel.x = -DASH_SPEED * rapier_config.scale;
+                    dash_state.last_dash = now;
+                }
+                dash_state.last_left_tap = now;
+            }
+            if keyboard_input.just_pressed(KeyCode::D) {
+                if off_cooldown && now - dash_state.last_right_tap < DASH_DOUBLE_TAP_WINDOW {
+                    rb_vel.linvel.x = DASH_SPEED * rapier_config.scale;
+                    dash_state.last_dash = now;
+                }
+                dash_state.last_right_tap = now;
+            }
+        }
+
+        if flying {
+            // Fly bypasses the jump counter entirely: holding Space just
+            // holds the player at full upward speed.
+            rb_vel.linvel.y = player.speed * rapier_config.scale;
+        } else if jump {
+            physics::jump(700.0, &mut rb_vel, rb_mprops);
+            grounded.jumps_used += 1;
         }
     }
 }
@@ -205,35 +569,38 @@ fn detect_char_interactable(
 
 pub struct GameOverEvent;
 
-pub fn player_fall_damage(
-    mut player_query: Query<(&mut Player, &Transform)>,
-    mut game_over_event: EventWriter<GameOverEvent>,
-) {
-    for (mut player, transform) in player_query.iter_mut() {
-        if transform.translation.y < -400.0 {
-            player.lives = 0;
-            game_over_event.send(GameOverEvent);
-            info!("Fell down hole")
-        }
-    }
-}
-
 pub fn player_collide_enemy(
     mut commands: Commands,
-    mut player_query: Query<(Entity, &mut Player)>,
+    mut player_query: Query<(Entity, &mut Player, &mut DefenseCharges)>,
     enemy_query: Query<Entity, With<Enemy>>,
     mut contact_events: EventReader<ContactEvent>,
     mut game_over_event: EventWriter<GameOverEvent>,
+    temp_effects: Res<TempEffectTimers>,
 ) {
     for contact_event in contact_events.iter() {
         if let ContactEvent::Started(h1, h2) = contact_event {
-            for (player_entity, mut player) in player_query.iter_mut() {
+            for (player_entity, mut player, mut defense) in player_query.iter_mut() {
                 for enemy_entity in enemy_query.iter() {
                     if h1.entity() == player_entity && h2.entity() == enemy_entity
                         || h2.entity() == player_entity && h1.entity() == enemy_entity
                     {
-                        player.lives -= 1;
                         commands.entity(enemy_entity).despawn();
+
+                        // TempInvicibility ignores the hit outright; Shield
+                        // and Armor charges absorb it before a life is lost.
+                        if temp_effects.is_active(INVINCIBILITY_TIMER) {
+                            continue;
+                        }
+                        if defense.shield > 0 {
+                            defense.shield -= 1;
+                            continue;
+                        }
+                        if defense.armor > 0 {
+                            defense.armor -= 1;
+                            continue;
+                        }
+
+                        player.lives -= 1;
                         if player.lives <= 0 {
                             game_over_event.send(GameOverEvent);
                         }
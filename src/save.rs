@@ -0,0 +1,211 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
+use crate::cheat_effects::AppliedCheatEffects;
+use crate::runner::player::{DefenseCharges, Player};
+use crate::states::GameStates;
+
+/// Where a run's progress is saved between sessions.
+const SAVE_FILE_PATH: &str = "save.toml";
+
+/// Loads a save on entering the run and writes one back out on leaving it,
+/// so progress survives quitting the game.
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameStates::Main).with_system(load_save.after("spawn_character")),
+        )
+        .add_system_set(SystemSet::on_exit(GameStates::Main).with_system(save_on_exit));
+    }
+}
+
+/// Restores a previous run's unlocked codes, generated code text, lives,
+/// speed, and defense charges onto the freshly spawned player. Leaves
+/// everything at its defaults if there's no save file yet (e.g. the very
+/// first run).
+///
+/// Restored codes are marked as already applied in `AppliedCheatEffects` so
+/// `cheat_effects::run_cheat_code_scripts` doesn't replay their scripts next
+/// update: the save already reflects every past `add_lives`/`add_armor`/etc.
+/// call, so re-running them here would double them up. That's also why
+/// `player.speed` (the cumulative result of every `set_speed` multiplier
+/// applied so far, e.g. from `SpeedBoost1..5`) is persisted directly instead
+/// of being left for the suppressed scripts to rebuild.
+fn load_save(
+    mut cheat_codes: ResMut<CheatCodeResource>,
+    mut applied_effects: ResMut<AppliedCheatEffects>,
+    mut player_query: Query<(&mut Player, &mut DefenseCharges)>,
+) {
+    let data = match SaveData::load_from_file(SAVE_FILE_PATH) {
+        Ok(data) => data,
+        Err(_) => return,
+    };
+
+    let lives = data.apply_to(&mut cheat_codes);
+    applied_effects.mark_applied(data.activated.iter().copied());
+    for (mut player, mut defense) in player_query.iter_mut() {
+        player.lives = lives;
+        player.speed = data.speed;
+        defense.armor = data.armor_charges;
+        defense.shield = data.shield_charges;
+    }
+}
+
+/// Captures the run's current state and writes it out so it can be resumed.
+fn save_on_exit(
+    cheat_codes: Res<CheatCodeResource>,
+    player_query: Query<(&Player, &DefenseCharges)>,
+) {
+    if let Some((player, defense)) = player_query.iter().next() {
+        let data = SaveData::capture(&cheat_codes, player, defense);
+        if let Err(err) = data.save_to_file(SAVE_FILE_PATH) {
+            error!("failed to write save file: {}", err);
+        }
+    }
+}
+
+/// Durable snapshot of a run: which codes are unlocked, the player's lives,
+/// speed, and remaining defense charges, and the exact generated text for
+/// each code, so a resumed run keeps the same secret strings instead of
+/// rerolling them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveData {
+    activated: Vec<CheatCodeKind>,
+    lives: i32,
+    speed: f32,
+    armor_charges: u8,
+    shield_charges: u8,
+    code_texts: Vec<(CheatCodeKind, String)>,
+}
+
+impl SaveData {
+    pub fn capture(
+        cheat_codes: &CheatCodeResource,
+        player: &Player,
+        defense: &DefenseCharges,
+    ) -> Self {
+        Self {
+            activated: cheat_codes.activated_kinds().to_vec(),
+            lives: player.lives,
+            speed: player.speed,
+            armor_charges: defense.armor,
+            shield_charges: defense.shield,
+            code_texts: cheat_codes
+                .codes
+                .iter()
+                .map(|(kind, code)| (*kind, code.text.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        let serialized = toml::to_string_pretty(self).map_err(SaveError::Serialize)?;
+        fs::write(path, serialized).map_err(SaveError::Io)
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, SaveError> {
+        let raw = fs::read_to_string(path).map_err(SaveError::Io)?;
+        toml::from_str(&raw).map_err(SaveError::Parse)
+    }
+
+    /// Restores the saved activation state and generated code text onto an
+    /// already-loaded catalog, and returns the saved lives total so the
+    /// caller can apply it to the spawned `Player`.
+    pub fn apply_to(&self, cheat_codes: &mut CheatCodeResource) -> i32 {
+        for (kind, text) in &self.code_texts {
+            cheat_codes.set_code_text(*kind, text.clone());
+        }
+        cheat_codes.restore_activated(self.activated.clone());
+        self.lives
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Serialize(toml::ser::Error),
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "failed to access save file: {}", err),
+            SaveError::Serialize(err) => write!(f, "failed to serialize save file: {}", err),
+            SaveError::Parse(err) => write!(f, "failed to parse save file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cheat_codes::CheatCodeResource;
+    use std::io::Write;
+
+    fn write_temp_catalog(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let mut file = fs::File::create(&path).unwrap();
+        file.write_all(
+            br#"
+                [[codes]]
+                kind = "Jump"
+                rarity = "Mandatory"
+                dependencies = []
+                image = "jump.png"
+                display_name = "Jump"
+
+                [[codes]]
+                kind = "Armor"
+                rarity = "Common"
+                dependencies = []
+                image = "armor.png"
+                display_name = "Armor"
+                script = "add_armor(fx, 3);"
+            "#,
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn save_data_round_trips_through_a_file() {
+        let catalog_path = write_temp_catalog("bevy_jam_save_round_trip_catalog.toml");
+        let mut cheat_codes = CheatCodeResource::from_file(&catalog_path).unwrap();
+        fs::remove_file(&catalog_path).ok();
+        cheat_codes.restore_activated(vec![CheatCodeKind::Jump, CheatCodeKind::Armor]);
+
+        let player = Player {
+            speed: 12.5,
+            acceleration: 0.09,
+            deceleration: 0.2,
+            lives: 2,
+        };
+        let defense = DefenseCharges {
+            armor: 2,
+            shield: 1,
+        };
+        let captured = SaveData::capture(&cheat_codes, &player, &defense);
+
+        let save_path = std::env::temp_dir().join("bevy_jam_save_round_trip_save.toml");
+        captured.save_to_file(&save_path).unwrap();
+        let loaded = SaveData::load_from_file(&save_path).unwrap();
+        fs::remove_file(&save_path).ok();
+
+        assert_eq!(loaded.lives, captured.lives);
+        assert_eq!(loaded.speed, captured.speed);
+        assert_eq!(loaded.armor_charges, captured.armor_charges);
+        assert_eq!(loaded.shield_charges, captured.shield_charges);
+        assert_eq!(loaded.activated, captured.activated);
+        assert_eq!(loaded.code_texts, captured.code_texts);
+    }
+}